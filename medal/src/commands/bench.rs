@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::Request;
+use tower::ServiceExt;
+use tracing::{error, info, warn};
+
+use super::serve::{build_app, ServeConfig};
+
+// Benchmark configuration. Mirrors the knobs a `wrk` invocation exposes: a
+// sample payload, how many connections to keep busy, and for how long.
+pub struct BenchConfig {
+    pub sample_path: PathBuf,
+    pub concurrency: usize,
+    pub duration_secs: u64,
+    pub encode_key: u8,
+    /// Remote base URL (e.g. `http://127.0.0.1:3000`). `None` drives the
+    /// in-process router directly, without a socket.
+    pub url: Option<String>,
+}
+
+// Per-client result, merged into the final report once every worker stops.
+struct ClientResult {
+    latencies: Vec<Duration>,
+    errors: u64,
+}
+
+// Fire repeated `POST /luau/decompile` requests from a pool of async clients
+// and print a `wrk`-style summary (throughput, latency distribution, errors).
+pub async fn bench_command(config: BenchConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let sample = std::fs::read(&config.sample_path)?;
+    if sample.len() < 4 {
+        return Err("sample bytecode too short (minimum 4 bytes)".into());
+    }
+
+    let sample = Arc::new(sample);
+    let duration = Duration::from_secs(config.duration_secs);
+    let target = config
+        .url
+        .clone()
+        .unwrap_or_else(|| "in-process router".to_string());
+
+    info!(
+        "🏋️  Running {}s bench @ {} with {} connections ({} byte sample)",
+        config.duration_secs,
+        target,
+        config.concurrency,
+        sample.len()
+    );
+
+    // A single shared router (in-process) or HTTP client (remote); both are
+    // cheaply cloneable per worker.
+    let app = if config.url.is_none() {
+        Some(build_app(&ServeConfig {
+            port: 0,
+            luau: true,
+            lua51: false,
+            decompile_timeout_secs: 30,
+            compression: false,
+            allowed_origins: Vec::new(),
+            allowed_methods: Vec::new(),
+        }))
+    } else {
+        None
+    };
+    let client = config.url.as_ref().map(|_| reqwest::Client::new());
+
+    // In-process requests pass through the server's concurrency limit layer,
+    // which caps concurrent decompiles at roughly the CPU count. Requesting more
+    // connections than that won't raise the decompiles actually in flight, so
+    // make the ceiling explicit rather than silently under-reporting.
+    let server_limit = if app.is_some() {
+        let cap = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        if config.concurrency > cap {
+            warn!(
+                "⚠️  in-process concurrency is bounded by the server limit ({}); \
+                 {} connections will contend for {} decompile permits",
+                cap, config.concurrency, cap
+            );
+        }
+        Some(cap)
+    } else {
+        None
+    };
+
+    let path = format!("/luau/decompile?encode_key={}", config.encode_key);
+    let remote_url = config.url.as_ref().map(|base| format!("{}{}", base, path));
+
+    let started = Instant::now();
+    let deadline = started + duration;
+
+    let mut handles = Vec::with_capacity(config.concurrency);
+    for _ in 0..config.concurrency {
+        let sample = Arc::clone(&sample);
+        let app = app.clone();
+        let client = client.clone();
+        let remote_url = remote_url.clone();
+        let path = path.clone();
+
+        handles.push(tokio::spawn(async move {
+            let mut result = ClientResult { latencies: Vec::new(), errors: 0 };
+            while Instant::now() < deadline {
+                let req_start = Instant::now();
+                let ok = match (&app, &client) {
+                    (Some(app), _) => drive_in_process(app.clone(), &path, &sample).await,
+                    (_, Some(client)) => {
+                        drive_remote(client, remote_url.as_deref().unwrap(), &sample).await
+                    }
+                    _ => unreachable!("either in-process app or remote client is set"),
+                };
+                if ok {
+                    result.latencies.push(req_start.elapsed());
+                } else {
+                    result.errors += 1;
+                }
+            }
+            result
+        }));
+    }
+
+    let mut latencies = Vec::new();
+    let mut errors = 0u64;
+    for handle in handles {
+        match handle.await {
+            Ok(result) => {
+                latencies.extend(result.latencies);
+                errors += result.errors;
+            }
+            Err(e) => error!("bench worker panicked: {}", e),
+        }
+    }
+
+    report(&mut latencies, errors, started.elapsed(), &target, config.concurrency, server_limit);
+    Ok(())
+}
+
+// Drive one request against the in-process router via `oneshot`, returning
+// whether it produced a 2xx response.
+async fn drive_in_process(app: axum::Router, path: &str, sample: &[u8]) -> bool {
+    let req = match Request::builder()
+        .method("POST")
+        .uri(path)
+        .body(Body::from(sample.to_vec()))
+    {
+        Ok(req) => req,
+        Err(_) => return false,
+    };
+    match app.oneshot(req).await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+// Drive one request against a remote server over HTTP.
+async fn drive_remote(client: &reqwest::Client, url: &str, sample: &[u8]) -> bool {
+    match client.post(url).body(sample.to_vec()).send().await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+// Print a summary modelled on standard HTTP benchmark tools.
+fn report(
+    latencies: &mut [Duration],
+    errors: u64,
+    elapsed: Duration,
+    target: &str,
+    concurrency: usize,
+    server_limit: Option<usize>,
+) {
+    latencies.sort_unstable();
+    let total = latencies.len() as u64 + errors;
+    let secs = elapsed.as_secs_f64();
+    let rps = if secs > 0.0 { total as f64 / secs } else { 0.0 };
+
+    println!("Running {:.1}s bench @ {}", secs, target);
+    match server_limit {
+        Some(cap) => println!(
+            "  {} connections (effective decompile concurrency: {})",
+            concurrency,
+            concurrency.min(cap)
+        ),
+        None => println!("  {} connections", concurrency),
+    }
+    println!("  {} requests in {:.2}s, {} errors", total, secs, errors);
+    println!("  Requests/sec: {:.2}", rps);
+    println!("  Latency distribution:");
+    println!("    p50: {}", fmt_ms(percentile(latencies, 0.50)));
+    println!("    p90: {}", fmt_ms(percentile(latencies, 0.90)));
+    println!("    p99: {}", fmt_ms(percentile(latencies, 0.99)));
+}
+
+// Nearest-rank percentile over the sorted latency slice.
+fn percentile(sorted: &[Duration], q: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    let idx = rank.saturating_sub(1).min(sorted.len() - 1);
+    Some(sorted[idx])
+}
+
+fn fmt_ms(d: Option<Duration>) -> String {
+    match d {
+        Some(d) => format!("{:.2} ms", d.as_secs_f64() * 1000.0),
+        None => "n/a".to_string(),
+    }
+}
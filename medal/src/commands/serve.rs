@@ -1,18 +1,110 @@
 use axum::{
     body::Bytes,
-    extract::Query,
-    http::{StatusCode},
+    extract::{Extension, Query},
+    http::{HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, post},
-    Router,
+    Json, Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use std::time::Duration;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{CorsLayer, Any};
 use tower_http::services::ServeDir;
 use tracing::{info, error, warn};
 use crate::commands::decompile_no_io;
 
+// Captured message from the most recent panic on this thread, set by the
+// panic hook installed in `install_panic_hook` and read back after a caught
+// unwind so the HTTP error body carries the real panic text.
+thread_local! {
+    static LAST_PANIC: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+// Install a process-wide panic hook (once) that records the panic payload and
+// logs a backtrace. The decompiler reaches deep into untrusted bytecode and a
+// malformed constant table or out-of-range jump can `panic!`/`unwrap` far from
+// the handler; `decompile_guarded` catches the unwind, and this hook supplies
+// the panic text for its error body (without it the body says "unknown panic").
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let msg = match info.payload().downcast_ref::<&str>() {
+                Some(s) => (*s).to_string(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(s) => s.clone(),
+                    None => "unknown panic".to_string(),
+                },
+            };
+            error!("🔥 decompiler panic: {}\n{:?}", msg, std::backtrace::Backtrace::force_capture());
+            LAST_PANIC.with(|cell| *cell.borrow_mut() = Some(msg));
+            // Keep the default hook behaviour (stderr) for local debugging.
+            prev(info);
+        }));
+    });
+}
+
+// Run the CPU-bound decompiler, converting any unwinding panic into a clean
+// `AppError::InternalError` instead of letting it tear down the connection.
+fn decompile_guarded(body: Bytes, encode_key: u8, lua51: bool) -> Result<String, AppError> {
+    let caught = panic::catch_unwind(AssertUnwindSafe(|| {
+        decompile_no_io(body, encode_key, lua51)
+    }));
+
+    match caught {
+        Ok(inner) => inner.map_err(|e| AppError::InternalError(e.to_string())),
+        Err(_) => {
+            let msg = LAST_PANIC
+                .with(|cell| cell.borrow_mut().take())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Err(AppError::InternalError(format!("decompiler panicked: {}", msg)))
+        }
+    }
+}
+
+// Run the synchronous, CPU-heavy decompiler off the async runtime.
+//
+// `decompile_no_io` blocks for the full duration of a decompile; running it
+// inline would starve the Tokio reactor and stall cheap endpoints like
+// `/health`. We hand it to `spawn_blocking` and bound the wall-clock with
+// `timeout`, returning a 503 to the client once it elapses.
+//
+// Note: the timeout bounds the *response*, not the blocking thread. Dropping
+// the `timeout` future does not cancel the already-running `spawn_blocking`
+// task, so an input that loops forever keeps occupying a blocking-pool thread
+// until `decompile_no_io` returns on its own. `decompile_no_io` has no
+// cooperative cancellation hook to check, so true cancellation isn't possible
+// here. The route concurrency limit does NOT bound these leaked threads: the
+// handler releases its permit as soon as it returns the 503, so the detached
+// task keeps running without one. The real ceiling on simultaneously-wedged
+// decompiles is the size of Tokio's blocking pool.
+async fn run_decompile(
+    body: Bytes,
+    encode_key: u8,
+    lua51: bool,
+    timeout_secs: u64,
+) -> Result<String, AppError> {
+    let task = tokio::task::spawn_blocking(move || decompile_guarded(body, encode_key, lua51));
+
+    match tokio::time::timeout(Duration::from_secs(timeout_secs), task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(join_err)) => {
+            Err(AppError::InternalError(format!("decompile task failed: {}", join_err)))
+        }
+        Err(_elapsed) => {
+            warn!("⏱️  decompilation exceeded {} s, shedding request", timeout_secs);
+            Err(AppError::Timeout(format!("decompilation exceeded {} s", timeout_secs)))
+        }
+    }
+}
+
 // Configuration
 #[derive(Deserialize, Clone)]
 pub struct ServeConfig {
@@ -22,6 +114,14 @@ pub struct ServeConfig {
     pub luau: bool,
     #[serde(default)]
     pub lua51: bool,
+    #[serde(default = "default_decompile_timeout_secs")]
+    pub decompile_timeout_secs: u64,
+    #[serde(default = "default_compression")]
+    pub compression: bool,
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
 }
 
 fn default_port() -> u16 {
@@ -31,6 +131,14 @@ fn default_port() -> u16 {
         .unwrap_or(3000)
 }
 
+fn default_decompile_timeout_secs() -> u64 {
+    30
+}
+
+fn default_compression() -> bool {
+    true
+}
+
 // Query parameters for encode key
 #[derive(Deserialize)]
 struct LuauQuery {
@@ -47,7 +155,29 @@ pub const fn default_encode_key() -> u8 {
 pub async fn serve(config: ServeConfig) -> Result<(), std::io::Error> {
     // Initialize tracing
     tracing_subscriber::fmt::init();
+
+    let app = build_app(&config);
+
+    // Bind to 0.0.0.0:PORT for Render compatibility
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    info!("🚀 Starting server on {}", addr);
+    info!("💡 Health check: http://{}/health", addr);
+    info!("📁 Serving static files from: ./public");
     
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+}
+
+// Assemble the fully-layered router. Kept separate from `serve` so the `bench`
+// subcommand can drive the same `Service` in-process (via `oneshot`) without
+// binding a socket.
+pub(crate) fn build_app(config: &ServeConfig) -> Router {
+    // Record decompiler panic messages for the 500s `decompile_guarded` returns.
+    // Installed here rather than in `serve` so the in-process `bench` path,
+    // which drives this router directly, also captures accurate panic messages.
+    install_panic_hook();
+
     let mut app = Router::new()
         .route("/health", get(health_check));
 
@@ -56,34 +186,103 @@ pub async fn serve(config: ServeConfig) -> Result<(), std::io::Error> {
     app = app.nest_service("/", ServeDir::new("public"));
 
     // Add CORS for browser frontend access
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any)
-        .max_age(std::time::Duration::from_secs(3600));
+    let cors = build_cors(config);
 
-    // Register endpoints based on feature flags
+    // Register decompile/verify endpoints in their own sub-router based on
+    // feature flags, so the concurrency limit below wraps only these CPU-heavy
+    // routes — `/health` and the static `ServeDir` stay outside it and keep
+    // responding even while every decompile permit is held.
+    let mut decompile_routes = Router::new();
     if config.luau {
         info!("✅ Luau endpoint: POST /luau/decompile?encode_key=<0-255>");
-        app = app.route("/luau/decompile", post(decompile_luau));
+        info!("✅ Luau verify endpoint: POST /luau/verify?encode_key=<0-255>");
+        decompile_routes = decompile_routes
+            .route("/luau/decompile", post(decompile_luau))
+            .route("/luau/verify", post(verify_luau));
     }
 
     if config.lua51 {
         info!("✅ Lua 5.1 endpoint: POST /lua51/decompile");
-        app = app.route("/lua51/decompile", post(decompile_lua51));
+        info!("✅ Lua 5.1 verify endpoint: POST /lua51/verify");
+        decompile_routes = decompile_routes
+            .route("/lua51/decompile", post(decompile_lua51))
+            .route("/lua51/verify", post(verify_lua51));
     }
 
-    let app = app.layer(cors);
+    // Bound concurrent decompiles to roughly the CPU count. `Router::layer`
+    // applies the layer to each route separately, so the global layer is used:
+    // its one semaphore is shared by every decompile/verify route. `ConcurrencyLimit`
+    // applies backpressure rather than shedding: once the permits are taken,
+    // further decompile requests queue (the layer's `poll_ready` returns
+    // `Pending`) until a permit frees up, instead of being rejected with a 503.
+    let max_concurrency = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    info!("🧵 Limiting concurrent decompiles to {}", max_concurrency);
 
-    // Bind to 0.0.0.0:PORT for Render compatibility
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
-    info!("🚀 Starting server on {}", addr);
-    info!("💡 Health check: http://{}/health", addr);
-    info!("📁 Serving static files from: ./public");
-    
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
+    let decompile_routes = decompile_routes
+        .layer(Extension(config.decompile_timeout_secs))
+        .layer(GlobalConcurrencyLimitLayer::new(max_concurrency));
+
+    let mut app = app.merge(decompile_routes);
+
+    // Decompiled Lua is one buffered, highly compressible `String`, so plain
+    // buffered compression (no per-chunk flushing) is all we need. Applied
+    // before the CORS layer so CORS headers are added on the outside and stay
+    // correct regardless of negotiated encoding.
+    if config.compression {
+        info!("🗜️  Response compression enabled (gzip/br/deflate)");
+        app = app.layer(CompressionLayer::new());
+    }
+
+    app.layer(cors)
+}
+
+// Build the CORS layer from the configured allowlist. An empty list falls back
+// to the permissive `Any` so existing single-frontend deployments keep working;
+// a non-empty list locks the decompiler down to the given origins/methods.
+fn build_cors(config: &ServeConfig) -> CorsLayer {
+    let mut cors = CorsLayer::new()
+        .allow_headers(Any)
+        .max_age(Duration::from_secs(3600));
+
+    if config.allowed_origins.is_empty() {
+        cors = cors.allow_origin(Any);
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .allowed_origins
+            .iter()
+            .filter_map(|o| match o.parse::<HeaderValue>() {
+                Ok(v) => Some(v),
+                Err(_) => {
+                    warn!("⚠️  Ignoring invalid CORS origin: {}", o);
+                    None
+                }
+            })
+            .collect();
+        info!("🔒 CORS restricted to origins: {:?}", config.allowed_origins);
+        cors = cors.allow_origin(origins);
+    }
+
+    if config.allowed_methods.is_empty() {
+        cors = cors.allow_methods(Any);
+    } else {
+        let methods: Vec<Method> = config
+            .allowed_methods
+            .iter()
+            .filter_map(|m| match m.parse::<Method>() {
+                Ok(v) => Some(v),
+                Err(_) => {
+                    warn!("⚠️  Ignoring invalid CORS method: {}", m);
+                    None
+                }
+            })
+            .collect();
+        info!("🔒 CORS restricted to methods: {:?}", config.allowed_methods);
+        cors = cors.allow_methods(methods);
+    }
+
+    cors
 }
 
 // Health check (required by Render)
@@ -94,14 +293,14 @@ async fn health_check() -> impl IntoResponse {
 // Luau decompilation handler
 async fn decompile_luau(
     Query(query): Query<LuauQuery>,
+    Extension(timeout_secs): Extension<u64>,
     body: Bytes,
 ) -> Result<String, AppError> {
     validate_body(&body)?;
-    
+
     info!("Decompiling Luau: {} bytes, encode_key={}", body.len(), query.encode_key);
-    
-    let result = decompile_no_io(body, query.encode_key, false)
-        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let result = run_decompile(body, query.encode_key, false, timeout_secs).await?;
 
     if result.trim().is_empty() {
         return Err(AppError::InternalError("Empty decompilation result".to_string()));
@@ -111,13 +310,15 @@ async fn decompile_luau(
 }
 
 // Lua 5.1 decompilation handler
-async fn decompile_lua51(body: Bytes) -> Result<String, AppError> {
+async fn decompile_lua51(
+    Extension(timeout_secs): Extension<u64>,
+    body: Bytes,
+) -> Result<String, AppError> {
     validate_body(&body)?;
-    
+
     info!("Decompiling Lua 5.1: {} bytes", body.len());
-    
-    let result = decompile_no_io(body, default_encode_key(), true)
-        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let result = run_decompile(body, default_encode_key(), true, timeout_secs).await?;
 
     if result.trim().is_empty() {
         return Err(AppError::InternalError("Empty decompilation result".to_string()));
@@ -126,6 +327,115 @@ async fn decompile_lua51(body: Bytes) -> Result<String, AppError> {
     Ok(result)
 }
 
+// Round-trip verification response: decompiled source plus whether it was
+// accepted by an embedded Lua compiler. `compiles` is null when no backend for
+// the request's dialect was compiled in (verification unavailable), `true`/`false`
+// otherwise; `error` carries the compiler message or the unavailable reason
+// (or, with `compiles: false`, the failure of the verify task itself).
+#[derive(Serialize)]
+struct VerifyResponse {
+    source: String,
+    compiles: Option<bool>,
+    error: Option<String>,
+    bytecode_len: usize,
+}
+
+// Luau round-trip: decompile, then recompile the source with mlua to confirm
+// the output is at least syntactically valid before the user relies on it.
+async fn verify_luau(
+    Query(query): Query<LuauQuery>,
+    Extension(timeout_secs): Extension<u64>,
+    body: Bytes,
+) -> Result<Json<VerifyResponse>, AppError> {
+    validate_body(&body)?;
+
+    let bytecode_len = body.len();
+    info!("Verifying Luau: {} bytes, encode_key={}", bytecode_len, query.encode_key);
+
+    let source = run_decompile(body, query.encode_key, false, timeout_secs).await?;
+    let (compiles, error) = verify_source(source.clone(), false).await;
+
+    Ok(Json(VerifyResponse { source, compiles, error, bytecode_len }))
+}
+
+// Lua 5.1 round-trip verification; see `verify_luau`.
+async fn verify_lua51(
+    Extension(timeout_secs): Extension<u64>,
+    body: Bytes,
+) -> Result<Json<VerifyResponse>, AppError> {
+    validate_body(&body)?;
+
+    let bytecode_len = body.len();
+    info!("Verifying Lua 5.1: {} bytes", bytecode_len);
+
+    let source = run_decompile(body, default_encode_key(), true, timeout_secs).await?;
+    let (compiles, error) = verify_source(source.clone(), true).await;
+
+    Ok(Json(VerifyResponse { source, compiles, error, bytecode_len }))
+}
+
+// Recompile decompiled source against the embedded Lua compiler for its dialect.
+//
+// `mlua` can only embed a single Lua version per binary (its `lua51`/`luau`
+// backends are mutually exclusive), so each verification backend is gated on its
+// own `verify-lua51`/`verify-luau` feature. The dialect is chosen by the
+// endpoint, not by which backend happens to be compiled in: a `/luau/verify`
+// request always checks against Luau, and if this build has no Luau backend it
+// returns `compiles: null` ("verification unavailable") rather than silently
+// compiling Luau output with a 5.1 compiler (and vice versa).
+//
+// Compilation can be expensive on a large `String`, so it runs on the blocking
+// pool like `decompile_no_io` rather than inline on the reactor thread.
+async fn verify_source(source: String, lua51: bool) -> (Option<bool>, Option<String>) {
+    let joined = tokio::task::spawn_blocking(move || {
+        if lua51 {
+            compile_lua51(&source)
+        } else {
+            compile_luau(&source)
+        }
+    })
+    .await;
+
+    match joined {
+        Ok(outcome) => outcome,
+        Err(join_err) => (Some(false), Some(format!("verify task failed: {}", join_err))),
+    }
+}
+
+#[cfg(all(feature = "verify-luau", feature = "verify-lua51"))]
+compile_error!(
+    "features `verify-luau` and `verify-lua51` are mutually exclusive: \
+     mlua can embed only one Lua version per binary"
+);
+
+#[cfg(feature = "verify-luau")]
+fn compile_luau(source: &str) -> (Option<bool>, Option<String>) {
+    let lua = mlua::Lua::new();
+    match lua.load(source).into_function() {
+        Ok(_) => (Some(true), None),
+        Err(e) => (Some(false), Some(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "verify-luau"))]
+fn compile_luau(_source: &str) -> (Option<bool>, Option<String>) {
+    (None, Some("verification unavailable for this dialect".to_string()))
+}
+
+#[cfg(feature = "verify-lua51")]
+fn compile_lua51(source: &str) -> (Option<bool>, Option<String>) {
+    let lua = mlua::Lua::new();
+    match lua.load(source).into_function() {
+        Ok(_) => (Some(true), None),
+        Err(e) => (Some(false), Some(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "verify-lua51"))]
+fn compile_lua51(_source: &str) -> (Option<bool>, Option<String>) {
+    (None, Some("verification unavailable for this dialect".to_string()))
+}
+
 // Input validation
 fn validate_body(body: &Bytes) -> Result<(), AppError> {
     if body.is_empty() {
@@ -142,6 +452,7 @@ fn validate_body(body: &Bytes) -> Result<(), AppError> {
 enum AppError {
     BadRequest(String),
     InternalError(String),
+    Timeout(String),
 }
 
 impl IntoResponse for AppError {
@@ -152,16 +463,33 @@ impl IntoResponse for AppError {
                 error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, msg)
             }
+            AppError::Timeout(msg) => {
+                warn!("Request timed out: {}", msg);
+                (StatusCode::SERVICE_UNAVAILABLE, msg)
+            }
         };
         (status, message).into_response()
     }
 }
 
 // CLI integration
-pub async fn serve_command(port: u16, luau: bool, lua51: bool) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn serve_command(
+    port: u16,
+    luau: bool,
+    lua51: bool,
+    cors_origins: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
     if !luau && !lua51 {
         return Err("❌ At least one of --luau or --lua51 must be enabled".into());
     }
-    serve(ServeConfig { port, luau, lua51 }).await?;
+    serve(ServeConfig {
+        port,
+        luau,
+        lua51,
+        decompile_timeout_secs: default_decompile_timeout_secs(),
+        compression: default_compression(),
+        allowed_origins: cors_origins,
+        allowed_methods: Vec::new(),
+    }).await?;
     Ok(())
 }